@@ -1,9 +1,19 @@
 use crate::common::{FQName, Identifier, OrderClause, RelationElement, Span};
 use itertools::Itertools;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
+// NOTE: the `serde` cargo feature referenced by the `cfg_attr`s below, and
+// the matching `Serialize`/`Deserialize` derives on `FQName`, `Identifier`,
+// `OrderClause`, `RelationElement` and `Span`, belong in `Cargo.toml` and
+// `common.rs` respectively. Neither file is part of this source tree, so
+// the feature cannot be declared or exercised here; the derives on this
+// file's own types are wired up and ready for when that lands.
+
 /// data for select statements
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Select {
     /// if true DISTINCT results
     pub distinct: bool,
@@ -15,12 +25,34 @@ pub struct Select {
     pub columns: Vec<SelectElement>,
     /// the where clause
     pub where_clause: Vec<RelationElement>,
+    /// the columns to group by.
+    ///
+    /// Note: the grammar/parser module that would populate this from CQL
+    /// text is not part of this source tree; until that wiring lands this
+    /// field can only be set by constructing a `Select` directly.
+    pub group_by: Vec<Identifier>,
     /// the optional ordering
     pub order: Option<OrderClause>,
+    /// the number of items to return per partition.
+    ///
+    /// Note: see `group_by` above -- parsing support is not wired up here.
+    pub per_partition_limit: Option<i32>,
     /// the number of items to return
     pub limit: Option<i32>,
     /// if true ALLOW FILTERING is displayed
     pub filtering: bool,
+    /// the span of the table name.
+    pub table_name_span: Span,
+    /// the span of the where clause, if one was present.
+    pub where_span: Option<Span>,
+    /// the span of the group by clause, if one was present.
+    pub group_by_span: Option<Span>,
+    /// the span of the order clause, if one was present.
+    pub order_span: Option<Span>,
+    /// the span of the per partition limit clause, if one was present.
+    pub per_partition_limit_span: Option<Span>,
+    /// the span of the limit clause, if one was present.
+    pub limit_span: Option<Span>,
 }
 
 impl Select {
@@ -53,13 +85,26 @@ impl Select {
             })
             .collect()
     }
+
+    /// return the aliases of the selected functions.  Functions without an
+    /// alias are omitted.
+    /// does not return columns.
+    pub fn function_alias(&self) -> Vec<Identifier> {
+        self.columns
+            .iter()
+            .filter_map(|e| match e {
+                SelectElement::Function(call) => call.alias.clone(),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 impl Display for Select {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "SELECT {}{}{} FROM {}{}{}{}{}",
+            "SELECT {}{}{} FROM {}{}{}{}{}{}{}",
             if self.distinct { "DISTINCT " } else { "" },
             if self.json { "JSON " } else { "" },
             self.columns.iter().join(", "),
@@ -69,9 +114,16 @@ impl Display for Select {
             } else {
                 "".to_string()
             },
+            if !self.group_by.is_empty() {
+                format!(" GROUP BY {}", self.group_by.iter().join(", "))
+            } else {
+                "".to_string()
+            },
             self.order
                 .as_ref()
                 .map_or("".to_string(), |x| format!(" ORDER BY {}", x)),
+            self.per_partition_limit
+                .map_or("".to_string(), |x| format!(" PER PARTITION LIMIT {}", x)),
             self.limit
                 .map_or("".to_string(), |x| format!(" LIMIT {}", x)),
             if self.filtering {
@@ -85,25 +137,143 @@ impl Display for Select {
 
 /// the selectable elements for a select statement
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SelectElement {
     /// All of the columns
-    Star,
+    Star(Span),
     /// a named column.  May have an alias specified.
     Column(Named),
-    /// a named column.  May have an alias specified.
-    Function(Named),
+    /// a function call.  May have an alias specified.
+    Function(FunctionCall),
 }
 
 impl Display for SelectElement {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            SelectElement::Star => write!(f, "*"),
-            SelectElement::Column(named) | SelectElement::Function(named) => write!(f, "{}", named),
+            SelectElement::Star(_) => write!(f, "*"),
+            SelectElement::Column(named) => write!(f, "{}", named),
+            SelectElement::Function(call) => write!(f, "{}", call),
+        }
+    }
+}
+
+/// a node whose extent in the original source text is known.
+pub trait Spanned {
+    /// returns the byte range in the original source text that this node covers.
+    fn span(&self) -> Span;
+}
+
+/// combines two spans into the smallest span that covers both.
+fn merge_spans(a: Span, b: Span) -> Span {
+    Span::new(a.start.min(b.start), a.end.max(b.end))
+}
+
+impl Spanned for SelectElement {
+    fn span(&self) -> Span {
+        match self {
+            SelectElement::Star(span) => *span,
+            SelectElement::Column(named) => named.span(),
+            SelectElement::Function(call) => call.span,
+        }
+    }
+}
+
+impl Spanned for Select {
+    fn span(&self) -> Span {
+        let mut span = self.table_name_span;
+        for column in &self.columns {
+            span = merge_spans(span, column.span());
+        }
+        for s in [
+            self.where_span,
+            self.group_by_span,
+            self.order_span,
+            self.per_partition_limit_span,
+            self.limit_span,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            span = merge_spans(span, s);
+        }
+        span
+    }
+}
+
+/// a function call used as a select element, e.g. `count(*)`, `writetime(col)`
+/// or `CAST(col AS int)`.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FunctionCall {
+    /// the name of the function.
+    pub name: FQName,
+    /// the arguments passed to the function.
+    pub args: Vec<FunctionArg>,
+    /// if true the arguments are preceded by `DISTINCT`.
+    pub distinct: bool,
+    /// the optional alias for the function result.
+    pub alias: Option<Identifier>,
+    /// the span covering the whole function call, including its arguments
+    /// and alias.
+    pub span: Span,
+}
+
+impl FunctionCall {
+    /// creates a `FunctionCall` with no alias and no `DISTINCT` keyword.
+    pub fn simple(name: FQName, args: Vec<FunctionArg>, span: Span) -> FunctionCall {
+        FunctionCall {
+            name,
+            args,
+            distinct: false,
+            alias: None,
+            span,
+        }
+    }
+}
+
+impl Display for FunctionCall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({}{})",
+            self.name,
+            if self.distinct { "DISTINCT " } else { "" },
+            self.args.iter().join(", ")
+        )?;
+        if let Some(alias) = &self.alias {
+            write!(f, " AS {}", alias)?;
+        }
+        Ok(())
+    }
+}
+
+/// a single argument to a `FunctionCall`.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FunctionArg {
+    /// the `*` argument, e.g. `count(*)`.
+    Star,
+    /// a bare column reference.
+    Column(Identifier),
+    /// a literal value, e.g. a string or numeric constant.
+    Literal(String),
+    /// a nested function call, e.g. `min(writetime(col))`.
+    Function(Box<FunctionCall>),
+}
+
+impl Display for FunctionArg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FunctionArg::Star => write!(f, "*"),
+            FunctionArg::Column(identifier) => write!(f, "{}", identifier),
+            FunctionArg::Literal(literal) => write!(f, "{}", literal),
+            FunctionArg::Function(call) => write!(f, "{}", call),
         }
     }
 }
 
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Named {
     pub name: Identifier,
     pub alias: Option<Identifier>,
@@ -142,20 +312,37 @@ impl Display for Named {
     }
 }
 
+impl Spanned for Named {
+    fn span(&self) -> Span {
+        match &self.alias {
+            None => self.name.span,
+            Some(alias) => merge_spans(self.name.span, alias.span),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::select::{Named, SelectElement, Span};
+    use crate::select::{
+        merge_spans, FunctionArg, FunctionCall, Named, Select, SelectElement, Span, Spanned,
+    };
+    use crate::common::{FQName, Identifier};
 
     #[test]
     fn test_select_element_display() {
-        assert_eq!("*", SelectElement::Star.to_string());
+        assert_eq!("*", SelectElement::Star(Span::from("*")).to_string());
         assert_eq!(
             "col",
             SelectElement::Column(Named::simple("col", Span::from("col"))).to_string()
         );
         assert_eq!(
-            "func",
-            SelectElement::Function(Named::simple("func", Span::from("func"))).to_string()
+            "count(*)",
+            SelectElement::Function(FunctionCall::simple(
+                FQName::simple("count"),
+                vec![FunctionArg::Star],
+                Span::from("count(*)")
+            ))
+            .to_string()
         );
         assert_eq!(
             "col AS alias",
@@ -168,14 +355,74 @@ mod tests {
             .to_string()
         );
         assert_eq!(
-            "func AS alias",
-            SelectElement::Function(Named::new(
-                "func",
-                Span::from("func"),
-                "alias",
-                Span::from("alias")
-            ))
+            "writetime(col) AS alias",
+            SelectElement::Function(FunctionCall {
+                name: FQName::simple("writetime"),
+                args: vec![FunctionArg::Column(Identifier::parse(
+                    "col",
+                    Span::from("col")
+                ))],
+                distinct: false,
+                alias: Some(Identifier::parse("alias", Span::from("alias"))),
+                span: Span::from("writetime(col) AS alias"),
+            })
             .to_string()
         );
+        assert_eq!(
+            "count(DISTINCT col)",
+            SelectElement::Function(FunctionCall {
+                name: FQName::simple("count"),
+                args: vec![FunctionArg::Column(Identifier::parse(
+                    "col",
+                    Span::from("col")
+                ))],
+                distinct: true,
+                alias: None,
+                span: Span::from("count(DISTINCT col)"),
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_select_element_span() {
+        let named = Named::new("col", Span::from("col"), "alias", Span::from("alias"));
+        assert_eq!(
+            merge_spans(Span::from("col"), Span::from("alias")),
+            SelectElement::Column(named).span()
+        );
+        assert_eq!(
+            Span::from("*"),
+            SelectElement::Star(Span::from("*")).span()
+        );
+    }
+
+    #[test]
+    fn test_select_span_includes_group_by_and_per_partition_limit() {
+        let select = Select {
+            distinct: false,
+            json: false,
+            table_name: FQName::simple("table"),
+            columns: vec![SelectElement::Column(Named::simple("col", Span::from("col")))],
+            where_clause: vec![],
+            group_by: vec![Identifier::parse("col", Span::from("col"))],
+            order: None,
+            per_partition_limit: Some(5),
+            limit: None,
+            filtering: false,
+            table_name_span: Span::from("table"),
+            where_span: None,
+            group_by_span: Some(Span::from("GROUP BY col")),
+            order_span: None,
+            per_partition_limit_span: Some(Span::from("PER PARTITION LIMIT 5")),
+            limit_span: None,
+        };
+
+        let span = select.span();
+        let expected = merge_spans(
+            merge_spans(Span::from("table"), Span::from("col")),
+            merge_spans(Span::from("GROUP BY col"), Span::from("PER PARTITION LIMIT 5")),
+        );
+        assert_eq!(expected, span);
     }
 }
@@ -0,0 +1,459 @@
+//! A visitor/mutable-visitor traversal layer over the [`Select`] AST.
+//!
+//! This mirrors the approach used by `datafusion-sqlparser`: a [`Visitor`]
+//! (and its mutable counterpart [`VisitorMut`]) exposes `pre_visit_*` and
+//! `post_visit_*` hooks for `Select`, `SelectElement`, `Named`,
+//! `RelationElement`, `FQName` and `Identifier` nodes, and
+//! [`Visit`]/[`VisitMut`] drive the walk over the tree.  Function calls,
+//! their arguments and the order clause have no hooks of their own: their
+//! names and column identifiers reach the `fq_name` and `identifier` hooks,
+//! and a `RelationElement` is passed to its hooks whole.  Returning
+//! [`ControlFlow::Break`] from any hook stops the walk early.
+use crate::common::{FQName, Identifier, RelationElement};
+use crate::select::{FunctionArg, FunctionCall, Named, Select, SelectElement};
+use std::ops::ControlFlow;
+
+/// runs `$expr` and returns early if it produced a [`ControlFlow::Break`].
+macro_rules! visit {
+    ($expr:expr) => {
+        match $expr {
+            ControlFlow::Continue(()) => {}
+            ControlFlow::Break(b) => return ControlFlow::Break(b),
+        }
+    };
+}
+
+/// a read-only visitor over the nodes of a `Select` statement.
+///
+/// Every hook defaults to a no-op that continues the walk, so implementors
+/// only need to override the hooks they care about.
+pub trait Visitor {
+    /// the type returned when a visit is short-circuited.
+    type Break;
+
+    fn pre_visit_select(&mut self, _select: &Select) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_select(&mut self, _select: &Select) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_select_element(&mut self, _element: &SelectElement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_select_element(&mut self, _element: &SelectElement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_named(&mut self, _named: &Named) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_named(&mut self, _named: &Named) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_relation(&mut self, _relation: &RelationElement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_relation(&mut self, _relation: &RelationElement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_fq_name(&mut self, _name: &FQName) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_fq_name(&mut self, _name: &FQName) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_identifier(&mut self, _identifier: &Identifier) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_identifier(&mut self, _identifier: &Identifier) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// a mutable visitor over the nodes of a `Select` statement, allowing
+/// in-place edits (e.g. qualifying an unqualified `table_name` or rewriting
+/// column names) during the walk.
+pub trait VisitorMut {
+    /// the type returned when a visit is short-circuited.
+    type Break;
+
+    fn pre_visit_select(&mut self, _select: &mut Select) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_select(&mut self, _select: &mut Select) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_select_element(
+        &mut self,
+        _element: &mut SelectElement,
+    ) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_select_element(
+        &mut self,
+        _element: &mut SelectElement,
+    ) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_named(&mut self, _named: &mut Named) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_named(&mut self, _named: &mut Named) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_relation(&mut self, _relation: &mut RelationElement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_relation(&mut self, _relation: &mut RelationElement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_fq_name(&mut self, _name: &mut FQName) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_fq_name(&mut self, _name: &mut FQName) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_identifier(&mut self, _identifier: &mut Identifier) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn post_visit_identifier(&mut self, _identifier: &mut Identifier) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// implemented by AST nodes that can be walked by a [`Visitor`].
+pub trait Visit {
+    fn accept<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break>;
+}
+
+/// implemented by AST nodes that can be walked (and edited) by a [`VisitorMut`].
+pub trait VisitMut {
+    fn accept_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break>;
+}
+
+impl Visit for Named {
+    fn accept<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visit!(visitor.pre_visit_named(self));
+        visit!(visitor.pre_visit_identifier(&self.name));
+        visit!(visitor.post_visit_identifier(&self.name));
+        if let Some(alias) = &self.alias {
+            visit!(visitor.pre_visit_identifier(alias));
+            visit!(visitor.post_visit_identifier(alias));
+        }
+        visitor.post_visit_named(self)
+    }
+}
+
+impl VisitMut for Named {
+    fn accept_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visit!(visitor.pre_visit_named(self));
+        visit!(visitor.pre_visit_identifier(&mut self.name));
+        visit!(visitor.post_visit_identifier(&mut self.name));
+        if let Some(alias) = &mut self.alias {
+            visit!(visitor.pre_visit_identifier(alias));
+            visit!(visitor.post_visit_identifier(alias));
+        }
+        visitor.post_visit_named(self)
+    }
+}
+
+impl Visit for FunctionArg {
+    fn accept<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            FunctionArg::Star => {}
+            FunctionArg::Column(identifier) => {
+                visit!(visitor.pre_visit_identifier(identifier));
+                visit!(visitor.post_visit_identifier(identifier));
+            }
+            FunctionArg::Literal(_) => {}
+            FunctionArg::Function(call) => visit!(call.accept(visitor)),
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl VisitMut for FunctionArg {
+    fn accept_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            FunctionArg::Star => {}
+            FunctionArg::Column(identifier) => {
+                visit!(visitor.pre_visit_identifier(identifier));
+                visit!(visitor.post_visit_identifier(identifier));
+            }
+            FunctionArg::Literal(_) => {}
+            FunctionArg::Function(call) => visit!(call.accept_mut(visitor)),
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl Visit for FunctionCall {
+    fn accept<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visit!(visitor.pre_visit_fq_name(&self.name));
+        visit!(visitor.post_visit_fq_name(&self.name));
+        for arg in &self.args {
+            visit!(arg.accept(visitor));
+        }
+        if let Some(alias) = &self.alias {
+            visit!(visitor.pre_visit_identifier(alias));
+            visit!(visitor.post_visit_identifier(alias));
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl VisitMut for FunctionCall {
+    fn accept_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visit!(visitor.pre_visit_fq_name(&mut self.name));
+        visit!(visitor.post_visit_fq_name(&mut self.name));
+        for arg in &mut self.args {
+            visit!(arg.accept_mut(visitor));
+        }
+        if let Some(alias) = &mut self.alias {
+            visit!(visitor.pre_visit_identifier(alias));
+            visit!(visitor.post_visit_identifier(alias));
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl Visit for SelectElement {
+    fn accept<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visit!(visitor.pre_visit_select_element(self));
+        match self {
+            SelectElement::Column(named) => visit!(named.accept(visitor)),
+            SelectElement::Function(call) => visit!(call.accept(visitor)),
+            SelectElement::Star(_) => {}
+        }
+        visitor.post_visit_select_element(self)
+    }
+}
+
+impl VisitMut for SelectElement {
+    fn accept_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visit!(visitor.pre_visit_select_element(self));
+        match self {
+            SelectElement::Column(named) => visit!(named.accept_mut(visitor)),
+            SelectElement::Function(call) => visit!(call.accept_mut(visitor)),
+            SelectElement::Star(_) => {}
+        }
+        visitor.post_visit_select_element(self)
+    }
+}
+
+impl Visit for Select {
+    fn accept<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visit!(visitor.pre_visit_select(self));
+
+        visit!(visitor.pre_visit_fq_name(&self.table_name));
+        visit!(visitor.post_visit_fq_name(&self.table_name));
+
+        for element in &self.columns {
+            visit!(element.accept(visitor));
+        }
+
+        for relation in &self.where_clause {
+            visit!(visitor.pre_visit_relation(relation));
+            visit!(visitor.post_visit_relation(relation));
+        }
+
+        for identifier in &self.group_by {
+            visit!(visitor.pre_visit_identifier(identifier));
+            visit!(visitor.post_visit_identifier(identifier));
+        }
+
+        if let Some(order) = &self.order {
+            visit!(visitor.pre_visit_identifier(&order.name));
+            visit!(visitor.post_visit_identifier(&order.name));
+        }
+
+        visitor.post_visit_select(self)
+    }
+}
+
+impl VisitMut for Select {
+    fn accept_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visit!(visitor.pre_visit_select(self));
+
+        visit!(visitor.pre_visit_fq_name(&mut self.table_name));
+        visit!(visitor.post_visit_fq_name(&mut self.table_name));
+
+        for element in &mut self.columns {
+            visit!(element.accept_mut(visitor));
+        }
+
+        for relation in &mut self.where_clause {
+            visit!(visitor.pre_visit_relation(relation));
+            visit!(visitor.post_visit_relation(relation));
+        }
+
+        for identifier in &mut self.group_by {
+            visit!(visitor.pre_visit_identifier(identifier));
+            visit!(visitor.post_visit_identifier(identifier));
+        }
+
+        if let Some(order) = &mut self.order {
+            visit!(visitor.pre_visit_identifier(&mut order.name));
+            visit!(visitor.post_visit_identifier(&mut order.name));
+        }
+
+        visitor.post_visit_select(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{FQName, Identifier, Span};
+    use crate::select::{FunctionArg, FunctionCall, Named, Select, SelectElement};
+
+    fn empty_select(columns: Vec<SelectElement>) -> Select {
+        Select {
+            distinct: false,
+            json: false,
+            table_name: FQName::simple("table"),
+            columns,
+            where_clause: vec![],
+            group_by: vec![],
+            order: None,
+            per_partition_limit: None,
+            limit: None,
+            filtering: false,
+            table_name_span: Span::from("table"),
+            where_span: None,
+            group_by_span: None,
+            order_span: None,
+            per_partition_limit_span: None,
+            limit_span: None,
+        }
+    }
+
+    struct Collector {
+        visited: Vec<String>,
+    }
+
+    impl Visitor for Collector {
+        type Break = ();
+
+        fn pre_visit_fq_name(&mut self, name: &FQName) -> ControlFlow<()> {
+            self.visited.push(name.to_string());
+            ControlFlow::Continue(())
+        }
+
+        fn pre_visit_identifier(&mut self, identifier: &Identifier) -> ControlFlow<()> {
+            self.visited.push(identifier.to_string());
+            ControlFlow::Continue(())
+        }
+    }
+
+    #[test]
+    fn test_accept_descends_into_function_args() {
+        let nested = FunctionCall::simple(
+            FQName::simple("writetime"),
+            vec![FunctionArg::Column(Identifier::parse(
+                "col",
+                Span::from("col"),
+            ))],
+            Span::from("writetime(col)"),
+        );
+        let element = SelectElement::Function(FunctionCall::simple(
+            FQName::simple("min"),
+            vec![FunctionArg::Function(Box::new(nested))],
+            Span::from("min(writetime(col))"),
+        ));
+
+        let mut collector = Collector { visited: vec![] };
+        let _ = element.accept(&mut collector);
+
+        assert_eq!(
+            vec!["min".to_string(), "writetime".to_string(), "col".to_string()],
+            collector.visited
+        );
+    }
+
+    struct BreakOnSecret {
+        visited: Vec<String>,
+    }
+
+    impl Visitor for BreakOnSecret {
+        type Break = ();
+
+        fn pre_visit_identifier(&mut self, identifier: &Identifier) -> ControlFlow<()> {
+            self.visited.push(identifier.to_string());
+            if identifier.to_string() == "secret" {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+    }
+
+    #[test]
+    fn test_accept_short_circuits_on_break() {
+        let select = empty_select(vec![
+            SelectElement::Column(Named::simple("col1", Span::from("col1"))),
+            SelectElement::Column(Named::simple("secret", Span::from("secret"))),
+            SelectElement::Column(Named::simple("col2", Span::from("col2"))),
+        ]);
+
+        let mut visitor = BreakOnSecret { visited: vec![] };
+        let result = select.accept(&mut visitor);
+
+        assert_eq!(ControlFlow::Break(()), result);
+        assert_eq!(
+            vec!["col1".to_string(), "secret".to_string()],
+            visitor.visited
+        );
+    }
+
+    #[test]
+    fn test_accept_visits_group_by() {
+        let mut select = empty_select(vec![]);
+        select.group_by = vec![
+            Identifier::parse("region", Span::from("region")),
+            Identifier::parse("year", Span::from("year")),
+        ];
+
+        let mut collector = Collector { visited: vec![] };
+        let _ = select.accept(&mut collector);
+
+        assert_eq!(
+            vec![
+                "table".to_string(),
+                "region".to_string(),
+                "year".to_string()
+            ],
+            collector.visited
+        );
+    }
+
+    struct SetFiltering;
+
+    impl VisitorMut for SetFiltering {
+        type Break = ();
+
+        fn pre_visit_select(&mut self, select: &mut Select) -> ControlFlow<()> {
+            select.filtering = true;
+            ControlFlow::Continue(())
+        }
+    }
+
+    #[test]
+    fn test_accept_mut_edits_select() {
+        let mut select = empty_select(vec![]);
+
+        let result = select.accept_mut(&mut SetFiltering);
+
+        assert_eq!(ControlFlow::Continue(()), result);
+        assert!(select.filtering);
+    }
+}